@@ -0,0 +1,485 @@
+//! DMG audio: the two square channels, wave and noise channels, mixed
+//! down to signed 16-bit stereo PCM for the I2S/DMA output path.
+//!
+//! The register map follows hardware: `NR1x` (`0xFF10`-`0xFF14`) for the
+//! sweep square channel, `NR2x` (`0xFF16`-`0xFF19`) for the plain square
+//! channel, `NR3x` (`0xFF1A`-`0xFF1E`) for the wave channel, `NR4x`
+//! (`0xFF20`-`0xFF23`) for noise, and `NR50`-`NR52` (`0xFF24`-`0xFF26`)
+//! for master volume/panning/power. Callers feed register writes in as
+//! they happen and pull PCM out at their own pace via `generate`.
+
+const SAMPLE_RATE: u32 = 44100;
+const FRAME_SEQUENCER_RATE: u32 = 512;
+
+const DUTY_TABLE: [[u8; 8]; 4] = [
+    [0, 0, 0, 0, 0, 0, 0, 1], // 12.5%
+    [1, 0, 0, 0, 0, 0, 0, 1], // 25%
+    [1, 0, 0, 0, 0, 1, 1, 1], // 50%
+    [0, 1, 1, 1, 1, 1, 1, 0], // 75%
+];
+
+/** Length counter + volume envelope, shared by the square, wave and
+ * noise channels. */
+#[derive(Default)]
+struct Envelope {
+    initial_volume: u8,
+    volume: u8,
+    increasing: bool,
+    period: u8,
+    timer: u8,
+}
+
+impl Envelope {
+    fn trigger(&mut self, nrx2: u8) {
+        self.initial_volume = nrx2 >> 4;
+        self.volume = self.initial_volume;
+        self.increasing = nrx2 & 0x08 != 0;
+        self.period = nrx2 & 0x07;
+        self.timer = self.period;
+    }
+
+    fn step(&mut self) {
+        if self.period == 0 {
+            return;
+        }
+        if self.timer > 0 {
+            self.timer -= 1;
+        }
+        if self.timer == 0 {
+            self.timer = self.period;
+            if self.increasing && self.volume < 15 {
+                self.volume += 1;
+            } else if !self.increasing && self.volume > 0 {
+                self.volume -= 1;
+            }
+        }
+    }
+}
+
+/** One of the two square-wave channels (NR1x/NR2x). */
+#[derive(Default)]
+struct Square {
+    enabled: bool,
+    dac_enabled: bool,
+    duty: u8,
+    duty_index: u8,
+    freq: u16,
+    freq_timer: i32,
+    length: u16,
+    length_enabled: bool,
+    envelope: Envelope,
+    // channel 1 only
+    sweep_period: u8,
+    sweep_negate: bool,
+    sweep_shift: u8,
+    sweep_timer: u8,
+    sweep_enabled: bool,
+    has_sweep: bool,
+}
+
+impl Square {
+    fn write_nrx1(&mut self, val: u8) {
+        self.duty = val >> 6;
+        self.length = 64 - (val & 0x3f) as u16;
+    }
+
+    fn write_nrx2(&mut self, val: u8) {
+        self.dac_enabled = val & 0xf8 != 0;
+        if !self.dac_enabled {
+            self.enabled = false;
+        }
+        self.envelope.trigger(val);
+    }
+
+    fn write_nrx3(&mut self, val: u8) {
+        self.freq = (self.freq & 0x700) | val as u16;
+    }
+
+    fn write_nrx4(&mut self, val: u8, frame_step: u8) {
+        self.freq = (self.freq & 0xff) | ((val as u16 & 0x7) << 8);
+        self.length_enabled = val & 0x40 != 0;
+        if val & 0x80 != 0 {
+            self.trigger(frame_step);
+        }
+    }
+
+    fn write_sweep(&mut self, val: u8) {
+        self.sweep_period = (val >> 4) & 0x7;
+        self.sweep_negate = val & 0x08 != 0;
+        self.sweep_shift = val & 0x7;
+    }
+
+    fn trigger(&mut self, _frame_step: u8) {
+        self.enabled = self.dac_enabled;
+        if self.length == 0 {
+            self.length = 64;
+        }
+        self.freq_timer = (2048 - self.freq as i32) * 4;
+        self.envelope.volume = self.envelope.initial_volume;
+        self.envelope.timer = self.envelope.period;
+        self.sweep_timer = if self.sweep_period != 0 { self.sweep_period } else { 8 };
+        self.sweep_enabled = self.has_sweep && (self.sweep_period != 0 || self.sweep_shift != 0);
+    }
+
+    fn step_length(&mut self) {
+        if self.length_enabled && self.length > 0 {
+            self.length -= 1;
+            if self.length == 0 {
+                self.enabled = false;
+            }
+        }
+    }
+
+    fn sweep_calc(&mut self) -> u16 {
+        let delta = self.freq >> self.sweep_shift;
+        if self.sweep_negate {
+            self.freq.wrapping_sub(delta)
+        } else {
+            self.freq + delta
+        }
+    }
+
+    fn step_sweep(&mut self) {
+        if !self.has_sweep {
+            return;
+        }
+        if self.sweep_timer > 0 {
+            self.sweep_timer -= 1;
+        }
+        if self.sweep_timer == 0 {
+            self.sweep_timer = if self.sweep_period != 0 { self.sweep_period } else { 8 };
+            if self.sweep_enabled && self.sweep_period != 0 {
+                let new_freq = self.sweep_calc();
+                if new_freq > 2047 {
+                    self.enabled = false;
+                } else if self.sweep_shift != 0 {
+                    self.freq = new_freq;
+                    self.freq_timer = (2048 - self.freq as i32) * 4;
+                    if self.sweep_calc() > 2047 {
+                        self.enabled = false;
+                    }
+                }
+            }
+        }
+    }
+
+    fn step(&mut self, t_cycles: i32) {
+        self.freq_timer -= t_cycles;
+        while self.freq_timer <= 0 {
+            self.freq_timer += (2048 - self.freq as i32) * 4;
+            self.duty_index = (self.duty_index + 1) % 8;
+        }
+    }
+
+    fn amplitude(&self) -> i16 {
+        if !self.enabled || !self.dac_enabled {
+            return 0;
+        }
+        let bit = DUTY_TABLE[self.duty as usize][self.duty_index as usize];
+        if bit != 0 { self.envelope.volume as i16 } else { 0 }
+    }
+}
+
+/** The wave channel (NR3x): plays back a 32-sample, 4-bit waveform
+ * from wave RAM (`0xFF30`-`0xFF3F`) instead of a duty cycle. */
+#[derive(Default)]
+struct Wave {
+    enabled: bool,
+    dac_enabled: bool,
+    length: u16,
+    length_enabled: bool,
+    volume_shift: u8, // 0 = mute, 1 = 100%, 2 = 50%, 3 = 25%
+    freq: u16,
+    freq_timer: i32,
+    sample_index: u8,
+    ram: [u8; 16],
+}
+
+impl Wave {
+    fn write_nr30(&mut self, val: u8) {
+        self.dac_enabled = val & 0x80 != 0;
+        if !self.dac_enabled {
+            self.enabled = false;
+        }
+    }
+
+    fn write_nr31(&mut self, val: u8) {
+        self.length = 256 - val as u16;
+    }
+
+    fn write_nr32(&mut self, val: u8) {
+        self.volume_shift = (val >> 5) & 0x3;
+    }
+
+    fn write_nr33(&mut self, val: u8) {
+        self.freq = (self.freq & 0x700) | val as u16;
+    }
+
+    fn write_nr34(&mut self, val: u8) {
+        self.freq = (self.freq & 0xff) | ((val as u16 & 0x7) << 8);
+        self.length_enabled = val & 0x40 != 0;
+        if val & 0x80 != 0 {
+            self.enabled = self.dac_enabled;
+            if self.length == 0 {
+                self.length = 256;
+            }
+            self.freq_timer = (2048 - self.freq as i32) * 2;
+            self.sample_index = 0;
+        }
+    }
+
+    fn step_length(&mut self) {
+        if self.length_enabled && self.length > 0 {
+            self.length -= 1;
+            if self.length == 0 {
+                self.enabled = false;
+            }
+        }
+    }
+
+    fn step(&mut self, t_cycles: i32) {
+        self.freq_timer -= t_cycles;
+        while self.freq_timer <= 0 {
+            self.freq_timer += (2048 - self.freq as i32) * 2;
+            self.sample_index = (self.sample_index + 1) % 32;
+        }
+    }
+
+    fn amplitude(&self) -> i16 {
+        if !self.enabled || !self.dac_enabled || self.volume_shift == 0 {
+            return 0;
+        }
+        let byte = self.ram[(self.sample_index / 2) as usize];
+        let nibble = if self.sample_index % 2 == 0 { byte >> 4 } else { byte & 0xf };
+        (nibble >> (self.volume_shift - 1)) as i16
+    }
+}
+
+const NOISE_DIVISORS: [i32; 8] = [8, 16, 32, 48, 64, 80, 96, 112];
+
+/** The noise channel (NR4x): a linear-feedback shift register clocked
+ * from a divisor/shift pair instead of a frequency. */
+#[derive(Default)]
+struct Noise {
+    enabled: bool,
+    dac_enabled: bool,
+    length: u16,
+    length_enabled: bool,
+    envelope: Envelope,
+    shift: u8,
+    width_mode: bool, // true = 7-bit LFSR, false = 15-bit
+    divisor_code: u8,
+    lfsr: u16,
+    freq_timer: i32,
+}
+
+impl Noise {
+    fn write_nr41(&mut self, val: u8) {
+        self.length = 64 - (val & 0x3f) as u16;
+    }
+
+    fn write_nr42(&mut self, val: u8) {
+        self.dac_enabled = val & 0xf8 != 0;
+        if !self.dac_enabled {
+            self.enabled = false;
+        }
+        self.envelope.trigger(val);
+    }
+
+    fn write_nr43(&mut self, val: u8) {
+        self.shift = val >> 4;
+        self.width_mode = val & 0x08 != 0;
+        self.divisor_code = val & 0x07;
+    }
+
+    fn write_nr44(&mut self, val: u8) {
+        self.length_enabled = val & 0x40 != 0;
+        if val & 0x80 != 0 {
+            self.enabled = self.dac_enabled;
+            if self.length == 0 {
+                self.length = 64;
+            }
+            self.envelope.volume = self.envelope.initial_volume;
+            self.envelope.timer = self.envelope.period;
+            self.lfsr = 0x7fff;
+            self.freq_timer = NOISE_DIVISORS[self.divisor_code as usize] << self.shift;
+        }
+    }
+
+    fn step_length(&mut self) {
+        if self.length_enabled && self.length > 0 {
+            self.length -= 1;
+            if self.length == 0 {
+                self.enabled = false;
+            }
+        }
+    }
+
+    fn step(&mut self, t_cycles: i32) {
+        self.freq_timer -= t_cycles;
+        while self.freq_timer <= 0 {
+            self.freq_timer += NOISE_DIVISORS[self.divisor_code as usize] << self.shift;
+            let xor_bit = (self.lfsr & 1) ^ ((self.lfsr >> 1) & 1);
+            self.lfsr = (self.lfsr >> 1) | (xor_bit << 14);
+            if self.width_mode {
+                self.lfsr = (self.lfsr & !0x40) | (xor_bit << 6);
+            }
+        }
+    }
+
+    fn amplitude(&self) -> i16 {
+        if !self.enabled || !self.dac_enabled {
+            return 0;
+        }
+        if self.lfsr & 1 == 0 { self.envelope.volume as i16 } else { 0 }
+    }
+}
+
+/** The frame sequencer clocks length at 256 Hz, sweep at 128 Hz and the
+ * envelope at 64 Hz, derived from a fixed 512 Hz base. */
+pub struct Apu {
+    ch1: Square,
+    ch2: Square,
+    ch3: Wave,
+    ch4: Noise,
+    nr50: u8,
+    nr51: u8,
+    power: bool,
+    frame_step: u8,
+    sample_counter: u32,
+}
+
+impl Apu {
+    pub fn new() -> Self {
+        let mut ch1 = Square::default();
+        ch1.has_sweep = true;
+        Self {
+            ch1,
+            ch2: Square::default(),
+            ch3: Wave::default(),
+            ch4: Noise::default(),
+            nr50: 0x77,
+            nr51: 0xf3,
+            power: true,
+            frame_step: 0,
+            sample_counter: 0,
+        }
+    }
+
+    pub fn push_register(&mut self, addr: u16, val: u8) {
+        match addr {
+            0xff10 => self.ch1.write_sweep(val),
+            0xff11 => self.ch1.write_nrx1(val),
+            0xff12 => self.ch1.write_nrx2(val),
+            0xff13 => self.ch1.write_nrx3(val),
+            0xff14 => self.ch1.write_nrx4(val, self.frame_step),
+            0xff16 => self.ch2.write_nrx1(val),
+            0xff17 => self.ch2.write_nrx2(val),
+            0xff18 => self.ch2.write_nrx3(val),
+            0xff19 => self.ch2.write_nrx4(val, self.frame_step),
+            0xff1a => self.ch3.write_nr30(val),
+            0xff1b => self.ch3.write_nr31(val),
+            0xff1c => self.ch3.write_nr32(val),
+            0xff1d => self.ch3.write_nr33(val),
+            0xff1e => self.ch3.write_nr34(val),
+            0xff20 => self.ch4.write_nr41(val),
+            0xff21 => self.ch4.write_nr42(val),
+            0xff22 => self.ch4.write_nr43(val),
+            0xff23 => self.ch4.write_nr44(val),
+            0xff24 => self.nr50 = val,
+            0xff25 => self.nr51 = val,
+            0xff26 => self.power = val & 0x80 != 0,
+            0xff30..=0xff3f => self.ch3.ram[(addr - 0xff30) as usize] = val,
+            _ => {}
+        }
+    }
+
+    /** Reads back whatever live state we track; registers this doesn't
+     * model yet (sweep/length/envelope/frequency) read as open bus
+     * (`0xFF`), same as real hardware's write-only bits. */
+    pub fn read_register(&self, addr: u16) -> u8 {
+        match addr {
+            0xff24 => self.nr50,
+            0xff25 => self.nr51,
+            0xff26 => {
+                0x70 | (self.power as u8) << 7
+                    | (self.ch4.enabled as u8) << 3
+                    | (self.ch3.enabled as u8) << 2
+                    | (self.ch2.enabled as u8) << 1
+                    | self.ch1.enabled as u8
+            }
+            0xff30..=0xff3f => self.ch3.ram[(addr - 0xff30) as usize],
+            _ => 0xff,
+        }
+    }
+
+    /** Advance the frame sequencer; called once every 512 Hz tick
+     * (i.e. once per 8192 CPU cycles at 4.194304 MHz). */
+    fn step_frame_sequencer(&mut self) {
+        if self.frame_step % 2 == 0 {
+            self.ch1.step_length();
+            self.ch2.step_length();
+            self.ch3.step_length();
+            self.ch4.step_length();
+        }
+        if self.frame_step % 4 == 2 {
+            self.ch1.step_sweep();
+        }
+        if self.frame_step == 7 {
+            self.ch1.envelope.step();
+            self.ch2.envelope.step();
+            self.ch4.envelope.step();
+        }
+        self.frame_step = (self.frame_step + 1) % 8;
+    }
+
+    /** Advance the synthesizer by `t_cycles` CPU clocks (called from
+     * the same step loop that drives the CPU/PPU/timer) and fill
+     * `out` with interleaved stereo samples at `SAMPLE_RATE`. */
+    pub fn generate(&mut self, t_cycles_per_sample: u32, out: &mut [i16]) {
+        let cycles_per_seq_tick = 4_194_304 / FRAME_SEQUENCER_RATE;
+        for frame in out.chunks_mut(2) {
+            if !self.power {
+                frame[0] = 0;
+                frame[1] = 0;
+                continue;
+            }
+            self.ch1.step(t_cycles_per_sample as i32);
+            self.ch2.step(t_cycles_per_sample as i32);
+            self.ch3.step(t_cycles_per_sample as i32);
+            self.ch4.step(t_cycles_per_sample as i32);
+
+            self.sample_counter += t_cycles_per_sample;
+            while self.sample_counter >= cycles_per_seq_tick {
+                self.sample_counter -= cycles_per_seq_tick;
+                self.step_frame_sequencer();
+            }
+
+            let left_vol = ((self.nr50 >> 4) & 0x7) as i16 + 1;
+            let right_vol = (self.nr50 & 0x7) as i16 + 1;
+            let ch1 = self.ch1.amplitude();
+            let ch2 = self.ch2.amplitude();
+            let ch3 = self.ch3.amplitude();
+            let ch4 = self.ch4.amplitude();
+
+            let mut left = 0i16;
+            let mut right = 0i16;
+            if self.nr51 & 0x10 != 0 { left += ch1; }
+            if self.nr51 & 0x20 != 0 { left += ch2; }
+            if self.nr51 & 0x40 != 0 { left += ch3; }
+            if self.nr51 & 0x80 != 0 { left += ch4; }
+            if self.nr51 & 0x01 != 0 { right += ch1; }
+            if self.nr51 & 0x02 != 0 { right += ch2; }
+            if self.nr51 & 0x04 != 0 { right += ch3; }
+            if self.nr51 & 0x08 != 0 { right += ch4; }
+
+            // Scale the per-channel sum (0-60 across 4 channels) and the
+            // NR50 volume factor (1-8) into the 16-bit range: the largest
+            // possible product is 60 * 8 * 64 = 30720, safely inside i16.
+            frame[0] = (left as i32 * left_vol as i32 * 64) as i16;
+            frame[1] = (right as i32 * right_vol as i32 * 64) as i16;
+        }
+    }
+}
+
+pub const SAMPLES_PER_SECOND: u32 = SAMPLE_RATE;
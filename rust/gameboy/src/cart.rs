@@ -0,0 +1,239 @@
+//! Cartridge ROM/RAM access: decodes the header to pick a Memory Bank
+//! Controller, then maps ROM and external RAM banks through it.
+//!
+//! Supports flat unbanked carts, MBC1 (ROM bank select at
+//! `0x2000-0x3FFF`, the 2-bit upper selector at `0x4000-0x5FFF` that
+//! either extends the ROM bank or selects a RAM bank depending on the
+//! mode flag at `0x6000-0x7FFF`) and MBC3 (same ROM/RAM banking
+//! windows, plus latched RTC registers mapped into `0xA000-0xBFFF`
+//! when the RAM/RTC select register points at one of them).
+
+use crate::save::SaveStore;
+
+const RAM_BANK_SIZE: usize = 0x2000;
+const MAX_RAM_BANKS: usize = 4; // up to 32 KiB, the common case on real carts
+
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum Mapper {
+    RomOnly,
+    Mbc1,
+    Mbc3,
+}
+
+/** MBC3's real-time-clock registers, latched on the 0x00->0x01 write
+ * sequence at `0x6000-0x7FFF`. There's no wall clock wired in, so
+ * `tick_second` must be called by the caller to advance them. */
+#[derive(Default, Clone, Copy)]
+struct Rtc {
+    seconds: u8,
+    minutes: u8,
+    hours: u8,
+    day_low: u8,
+    day_high: u8, // bit 0: day high bit, bit 6: halt, bit 7: day carry
+    latched: [u8; 5],
+    latch_step: u8,
+}
+
+impl Rtc {
+    fn tick_second(&mut self) {
+        if self.day_high & 0x40 != 0 {
+            return; // halted
+        }
+        self.seconds += 1;
+        if self.seconds == 60 {
+            self.seconds = 0;
+            self.minutes += 1;
+        }
+        if self.minutes == 60 {
+            self.minutes = 0;
+            self.hours += 1;
+        }
+        if self.hours == 24 {
+            self.hours = 0;
+            let (day, overflow) = self.day_low.overflowing_add(1);
+            self.day_low = day;
+            if overflow {
+                if self.day_high & 1 != 0 {
+                    self.day_high |= 0x80; // day counter carry
+                }
+                self.day_high ^= 1;
+            }
+        }
+    }
+
+    fn latch(&mut self) {
+        self.latched = [self.seconds, self.minutes, self.hours, self.day_low, self.day_high];
+    }
+
+    fn write_latch_trigger(&mut self, val: u8) {
+        if self.latch_step == 0 && val == 0x00 {
+            self.latch_step = 1;
+        } else if self.latch_step == 1 && val == 0x01 {
+            self.latch();
+            self.latch_step = 0;
+        } else {
+            self.latch_step = 0;
+        }
+    }
+}
+
+/** A cartridge, with whichever mapper its header calls for. `S` is the
+ * backing store battery-backed RAM is persisted to. */
+pub struct Cart<'a, S: SaveStore> {
+    rom: &'a [u8],
+    mapper: Mapper,
+    rom_bank: usize,
+    rom_banks: usize,
+    ram: [u8; MAX_RAM_BANKS * RAM_BANK_SIZE],
+    ram_banks: usize,
+    ram_bank: usize,
+    ram_enabled: bool,
+    banking_mode_ram: bool, // MBC1: true selects RAM banking over upper ROM bits
+    rtc: Rtc,
+    has_battery: bool,
+    store: S,
+    save_slot: u32,
+}
+
+impl<'a, S: SaveStore> Cart<'a, S> {
+    pub fn new(rom: &'a [u8], store: S) -> Self {
+        let cart_type = *rom.get(0x147).unwrap_or(&0);
+        let mapper = match cart_type {
+            0x01..=0x03 => Mapper::Mbc1,
+            0x0f..=0x13 => Mapper::Mbc3,
+            _ => Mapper::RomOnly,
+        };
+        let has_battery = matches!(cart_type, 0x03 | 0x06 | 0x09 | 0x0d | 0x0f | 0x10 | 0x13 | 0x1b | 0x1e);
+
+        let rom_size_code = *rom.get(0x148).unwrap_or(&0);
+        let rom_banks = 2usize << rom_size_code.min(8);
+
+        let ram_size_code = *rom.get(0x149).unwrap_or(&0);
+        let ram_banks = match ram_size_code {
+            1 => 1, // 2 KiB, partial bank; treated as one bank here
+            2 => 1, // 8 KiB
+            3 => 4, // 32 KiB
+            _ => 0,
+        }
+        .min(MAX_RAM_BANKS);
+
+        let mut cart = Self {
+            rom,
+            mapper,
+            rom_bank: 1,
+            rom_banks,
+            ram: [0; MAX_RAM_BANKS * RAM_BANK_SIZE],
+            ram_banks,
+            ram_bank: 0,
+            ram_enabled: false,
+            banking_mode_ram: false,
+            rtc: Rtc::default(),
+            has_battery,
+            store,
+            save_slot: 0,
+        };
+        if has_battery && ram_banks > 0 {
+            let len = ram_banks * RAM_BANK_SIZE;
+            cart.store.load(cart.save_slot, &mut cart.ram[..len]);
+        }
+        cart
+    }
+
+    fn rom_offset(&self, addr: u16) -> usize {
+        match addr {
+            0x0000..=0x3fff => addr as usize,
+            _ => self.rom_bank.max(1) % self.rom_banks.max(1) * 0x4000 + (addr as usize - 0x4000),
+        }
+    }
+
+    pub fn read(&self, addr: u16) -> u8 {
+        match addr {
+            0x0000..=0x7fff => *self.rom.get(self.rom_offset(addr)).unwrap_or(&0xff),
+            0xa000..=0xbfff => {
+                if self.mapper == Mapper::Mbc3 && self.ram_bank >= 0x08 {
+                    match self.ram_bank {
+                        0x08 => self.rtc.latched[0],
+                        0x09 => self.rtc.latched[1],
+                        0x0a => self.rtc.latched[2],
+                        0x0b => self.rtc.latched[3],
+                        0x0c => self.rtc.latched[4],
+                        _ => 0xff,
+                    }
+                } else if self.ram_enabled && self.ram_banks > 0 {
+                    let bank = self.ram_bank % self.ram_banks;
+                    self.ram[bank * RAM_BANK_SIZE + (addr as usize - 0xa000)]
+                } else {
+                    0xff
+                }
+            }
+            _ => 0xff,
+        }
+    }
+
+    pub fn write(&mut self, addr: u16, val: u8) {
+        match (self.mapper, addr) {
+            (_, 0x0000..=0x1fff) => {
+                let was_enabled = self.ram_enabled;
+                self.ram_enabled = val & 0x0f == 0x0a;
+                if was_enabled && !self.ram_enabled {
+                    self.persist();
+                }
+            }
+            (Mapper::Mbc1, 0x2000..=0x3fff) => {
+                let bank = (val & 0x1f) as usize;
+                self.rom_bank = (self.rom_bank & !0x1f) | bank.max(1);
+            }
+            (Mapper::Mbc3, 0x2000..=0x3fff) => {
+                self.rom_bank = (val & 0x7f).max(1) as usize;
+            }
+            (Mapper::Mbc1, 0x4000..=0x5fff) => {
+                if self.banking_mode_ram {
+                    self.ram_bank = (val & 0x3) as usize;
+                } else {
+                    self.rom_bank = (self.rom_bank & 0x1f) | ((val as usize & 0x3) << 5);
+                }
+            }
+            (Mapper::Mbc3, 0x4000..=0x5fff) => {
+                self.ram_bank = val as usize;
+            }
+            (Mapper::Mbc1, 0x6000..=0x7fff) => {
+                self.banking_mode_ram = val & 1 != 0;
+            }
+            (Mapper::Mbc3, 0x6000..=0x7fff) => {
+                self.rtc.write_latch_trigger(val);
+            }
+            (_, 0xa000..=0xbfff) => {
+                if self.mapper == Mapper::Mbc3 && self.ram_bank >= 0x08 {
+                    match self.ram_bank {
+                        0x08 => self.rtc.seconds = val,
+                        0x09 => self.rtc.minutes = val,
+                        0x0a => self.rtc.hours = val,
+                        0x0b => self.rtc.day_low = val,
+                        0x0c => self.rtc.day_high = val,
+                        _ => {}
+                    }
+                } else if self.ram_enabled && self.ram_banks > 0 {
+                    let bank = self.ram_bank % self.ram_banks;
+                    self.ram[bank * RAM_BANK_SIZE + (addr as usize - 0xa000)] = val;
+                }
+            }
+            _ => {}
+        }
+    }
+
+    /** Advance the MBC3 real-time clock by one second; a no-op for
+     * other mappers. Call this at whatever cadence the caller tracks
+     * wall-clock time (it isn't driven by CPU cycles). */
+    pub fn tick_rtc_second(&mut self) {
+        if self.mapper == Mapper::Mbc3 {
+            self.rtc.tick_second();
+        }
+    }
+
+    fn persist(&mut self) {
+        if self.has_battery && self.ram_banks > 0 {
+            let len = self.ram_banks * RAM_BANK_SIZE;
+            self.store.save(self.save_slot, &self.ram[..len]);
+        }
+    }
+}
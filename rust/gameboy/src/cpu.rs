@@ -0,0 +1,510 @@
+//! Cycle-accurate(-ish) LR35902 core: registers, the full opcode table
+//! plus its CB-prefixed extension, and interrupt dispatch.
+
+use crate::mmu::Mmu;
+use crate::save::SaveStore;
+
+const FLAG_Z: u8 = 0x80;
+const FLAG_N: u8 = 0x40;
+const FLAG_H: u8 = 0x20;
+const FLAG_C: u8 = 0x10;
+
+#[derive(Default)]
+struct Regs {
+    a: u8,
+    f: u8,
+    b: u8,
+    c: u8,
+    d: u8,
+    e: u8,
+    h: u8,
+    l: u8,
+    sp: u16,
+    pc: u16,
+}
+
+impl Regs {
+    fn bc(&self) -> u16 { (self.b as u16) << 8 | self.c as u16 }
+    fn de(&self) -> u16 { (self.d as u16) << 8 | self.e as u16 }
+    fn hl(&self) -> u16 { (self.h as u16) << 8 | self.l as u16 }
+    fn af(&self) -> u16 { (self.a as u16) << 8 | (self.f & 0xf0) as u16 }
+    fn set_bc(&mut self, v: u16) { self.b = (v >> 8) as u8; self.c = v as u8; }
+    fn set_de(&mut self, v: u16) { self.d = (v >> 8) as u8; self.e = v as u8; }
+    fn set_hl(&mut self, v: u16) { self.h = (v >> 8) as u8; self.l = v as u8; }
+    fn set_af(&mut self, v: u16) { self.a = (v >> 8) as u8; self.f = (v as u8) & 0xf0; }
+}
+
+/** The CPU core. `halted`/`ime` track the low-power and interrupt-master
+ * states that the HALT/EI/DI/RETI instructions manipulate. */
+pub struct Cpu {
+    r: Regs,
+    ime: bool,
+    ime_pending: bool,
+    halted: bool,
+}
+
+impl Cpu {
+    pub fn new() -> Self {
+        // Post-bootrom register state for a DMG.
+        let mut r = Regs::default();
+        r.set_af(0x01b0);
+        r.set_bc(0x0013);
+        r.set_de(0x00d8);
+        r.set_hl(0x014d);
+        r.sp = 0xfffe;
+        r.pc = 0x0100;
+        Self { r, ime: false, ime_pending: false, halted: false }
+    }
+
+    fn flag(&self, mask: u8) -> bool { self.r.f & mask != 0 }
+    fn set_flag(&mut self, mask: u8, set: bool) {
+        if set { self.r.f |= mask; } else { self.r.f &= !mask; }
+    }
+
+    fn fetch<S: SaveStore>(&mut self, mmu: &Mmu<S>) -> u8 {
+        let b = mmu.read(self.r.pc);
+        self.r.pc = self.r.pc.wrapping_add(1);
+        b
+    }
+
+    fn fetch16<S: SaveStore>(&mut self, mmu: &Mmu<S>) -> u16 {
+        let lo = self.fetch(mmu) as u16;
+        let hi = self.fetch(mmu) as u16;
+        hi << 8 | lo
+    }
+
+    fn push<S: SaveStore>(&mut self, mmu: &mut Mmu<S>, val: u16) {
+        self.r.sp = self.r.sp.wrapping_sub(2);
+        mmu.write16(self.r.sp, val);
+    }
+
+    fn pop<S: SaveStore>(&mut self, mmu: &Mmu<S>) -> u16 {
+        let val = mmu.read16(self.r.sp);
+        self.r.sp = self.r.sp.wrapping_add(2);
+        val
+    }
+
+    /** Execute one instruction (servicing a pending interrupt first if
+     * any is both requested and enabled) and return the number of
+     * machine cycles it took. */
+    pub fn step<S: SaveStore>(&mut self, mmu: &mut Mmu<S>) -> u32 {
+        if let Some(cycles) = self.service_interrupt(mmu) {
+            return cycles;
+        }
+        if self.halted {
+            return 4;
+        }
+        if self.ime_pending {
+            self.ime = true;
+            self.ime_pending = false;
+        }
+        let op = self.fetch(mmu);
+        self.execute(mmu, op)
+    }
+
+    fn service_interrupt<S: SaveStore>(&mut self, mmu: &mut Mmu<S>) -> Option<u32> {
+        let pending = mmu.if_reg & mmu.ie_reg & 0x1f;
+        if pending == 0 {
+            return None;
+        }
+        self.halted = false;
+        if !self.ime {
+            return None;
+        }
+        let bit = pending.trailing_zeros();
+        mmu.if_reg &= !(1 << bit);
+        self.ime = false;
+        self.push(mmu, self.r.pc);
+        self.r.pc = 0x0040 + 8 * bit as u16;
+        Some(20)
+    }
+
+    fn add8(&mut self, a: u8, b: u8, carry: u8) -> u8 {
+        let (r1, c1) = a.overflowing_add(b);
+        let (r2, c2) = r1.overflowing_add(carry);
+        self.set_flag(FLAG_Z, r2 == 0);
+        self.set_flag(FLAG_N, false);
+        self.set_flag(FLAG_H, (a & 0xf) + (b & 0xf) + carry > 0xf);
+        self.set_flag(FLAG_C, c1 || c2);
+        r2
+    }
+
+    fn sub8(&mut self, a: u8, b: u8, carry: u8) -> u8 {
+        let (r1, c1) = a.overflowing_sub(b);
+        let (r2, c2) = r1.overflowing_sub(carry);
+        self.set_flag(FLAG_Z, r2 == 0);
+        self.set_flag(FLAG_N, true);
+        self.set_flag(FLAG_H, (a & 0xf) < (b & 0xf) + carry);
+        self.set_flag(FLAG_C, c1 || c2);
+        r2
+    }
+
+    fn and8(&mut self, a: u8, b: u8) -> u8 {
+        let r = a & b;
+        self.r.f = 0;
+        self.set_flag(FLAG_Z, r == 0);
+        self.set_flag(FLAG_H, true);
+        r
+    }
+
+    fn or8(&mut self, a: u8, b: u8) -> u8 {
+        let r = a | b;
+        self.r.f = 0;
+        self.set_flag(FLAG_Z, r == 0);
+        r
+    }
+
+    fn xor8(&mut self, a: u8, b: u8) -> u8 {
+        let r = a ^ b;
+        self.r.f = 0;
+        self.set_flag(FLAG_Z, r == 0);
+        r
+    }
+
+    fn inc8(&mut self, v: u8) -> u8 {
+        let r = v.wrapping_add(1);
+        self.set_flag(FLAG_Z, r == 0);
+        self.set_flag(FLAG_N, false);
+        self.set_flag(FLAG_H, v & 0xf == 0xf);
+        r
+    }
+
+    fn dec8(&mut self, v: u8) -> u8 {
+        let r = v.wrapping_sub(1);
+        self.set_flag(FLAG_Z, r == 0);
+        self.set_flag(FLAG_N, true);
+        self.set_flag(FLAG_H, v & 0xf == 0);
+        r
+    }
+
+    fn add16(&mut self, a: u16, b: u16) -> u16 {
+        let (r, c) = a.overflowing_add(b);
+        self.set_flag(FLAG_N, false);
+        self.set_flag(FLAG_H, (a & 0xfff) + (b & 0xfff) > 0xfff);
+        self.set_flag(FLAG_C, c);
+        r
+    }
+
+    fn get_r8<S: SaveStore>(&self, mmu: &Mmu<S>, idx: u8) -> u8 {
+        match idx {
+            0 => self.r.b,
+            1 => self.r.c,
+            2 => self.r.d,
+            3 => self.r.e,
+            4 => self.r.h,
+            5 => self.r.l,
+            6 => mmu.read(self.r.hl()),
+            _ => self.r.a,
+        }
+    }
+
+    fn set_r8<S: SaveStore>(&mut self, mmu: &mut Mmu<S>, idx: u8, val: u8) {
+        match idx {
+            0 => self.r.b = val,
+            1 => self.r.c = val,
+            2 => self.r.d = val,
+            3 => self.r.e = val,
+            4 => self.r.h = val,
+            5 => self.r.l = val,
+            6 => mmu.write(self.r.hl(), val),
+            _ => self.r.a = val,
+        }
+    }
+
+    fn cond(&self, idx: u8) -> bool {
+        match idx {
+            0 => !self.flag(FLAG_Z),
+            1 => self.flag(FLAG_Z),
+            2 => !self.flag(FLAG_C),
+            _ => self.flag(FLAG_C),
+        }
+    }
+
+    fn execute<S: SaveStore>(&mut self, mmu: &mut Mmu<S>, op: u8) -> u32 {
+        match op {
+            0x00 => 4, // NOP
+            0x76 => { self.halted = true; 4 } // HALT
+            0xf3 => { self.ime = false; self.ime_pending = false; 4 } // DI
+            0xfb => { self.ime_pending = true; 4 } // EI
+            0x10 => { self.fetch(mmu); 4 } // STOP (skip following byte)
+
+            // 8-bit immediate loads: LD r, d8
+            0x06 => { let v = self.fetch(mmu); self.r.b = v; 8 }
+            0x0e => { let v = self.fetch(mmu); self.r.c = v; 8 }
+            0x16 => { let v = self.fetch(mmu); self.r.d = v; 8 }
+            0x1e => { let v = self.fetch(mmu); self.r.e = v; 8 }
+            0x26 => { let v = self.fetch(mmu); self.r.h = v; 8 }
+            0x2e => { let v = self.fetch(mmu); self.r.l = v; 8 }
+            0x36 => { let v = self.fetch(mmu); mmu.write(self.r.hl(), v); 12 }
+            0x3e => { let v = self.fetch(mmu); self.r.a = v; 8 }
+
+            // 16-bit immediate loads
+            0x01 => { let v = self.fetch16(mmu); self.r.set_bc(v); 12 }
+            0x11 => { let v = self.fetch16(mmu); self.r.set_de(v); 12 }
+            0x21 => { let v = self.fetch16(mmu); self.r.set_hl(v); 12 }
+            0x31 => { let v = self.fetch16(mmu); self.r.sp = v; 12 }
+
+            // LD (nn), SP / LD SP, HL / LD HL, SP+r8
+            0x08 => { let a = self.fetch16(mmu); mmu.write16(a, self.r.sp); 20 }
+            0xf9 => { self.r.sp = self.r.hl(); 8 }
+            0xf8 => {
+                let off = self.fetch(mmu) as i8 as i16;
+                let sp = self.r.sp as i16;
+                let res = sp.wrapping_add(off) as u16;
+                self.r.f = 0;
+                self.set_flag(FLAG_H, (self.r.sp & 0xf) as i16 + (off & 0xf) > 0xf);
+                self.set_flag(FLAG_C, (self.r.sp & 0xff) as i16 + (off & 0xff) > 0xff);
+                self.r.set_hl(res);
+                12
+            }
+
+            // LD (BC/DE), A and LD A, (BC/DE)
+            0x02 => { mmu.write(self.r.bc(), self.r.a); 8 }
+            0x12 => { mmu.write(self.r.de(), self.r.a); 8 }
+            0x0a => { self.r.a = mmu.read(self.r.bc()); 8 }
+            0x1a => { self.r.a = mmu.read(self.r.de()); 8 }
+
+            // LD (HL+/-), A and LD A, (HL+/-)
+            0x22 => { let a = self.r.hl(); mmu.write(a, self.r.a); self.r.set_hl(a.wrapping_add(1)); 8 }
+            0x32 => { let a = self.r.hl(); mmu.write(a, self.r.a); self.r.set_hl(a.wrapping_sub(1)); 8 }
+            0x2a => { let a = self.r.hl(); self.r.a = mmu.read(a); self.r.set_hl(a.wrapping_add(1)); 8 }
+            0x3a => { let a = self.r.hl(); self.r.a = mmu.read(a); self.r.set_hl(a.wrapping_sub(1)); 8 }
+
+            // LDH
+            0xe0 => { let a = 0xff00 | self.fetch(mmu) as u16; mmu.write(a, self.r.a); 12 }
+            0xf0 => { let a = 0xff00 | self.fetch(mmu) as u16; self.r.a = mmu.read(a); 12 }
+            0xe2 => { mmu.write(0xff00 | self.r.c as u16, self.r.a); 8 }
+            0xf2 => { self.r.a = mmu.read(0xff00 | self.r.c as u16); 8 }
+            0xea => { let a = self.fetch16(mmu); mmu.write(a, self.r.a); 16 }
+            0xfa => { let a = self.fetch16(mmu); self.r.a = mmu.read(a); 16 }
+
+            // INC/DEC r8 (and (HL)), keyed the same way as execute_block's
+            // get_r8/set_r8 index.
+            0x04 | 0x0c | 0x14 | 0x1c | 0x24 | 0x2c | 0x34 | 0x3c => {
+                let idx = (op >> 3) & 0x7;
+                let v = self.get_r8(mmu, idx);
+                let r = self.inc8(v);
+                self.set_r8(mmu, idx, r);
+                if idx == 6 { 12 } else { 4 }
+            }
+            0x05 | 0x0d | 0x15 | 0x1d | 0x25 | 0x2d | 0x35 | 0x3d => {
+                let idx = (op >> 3) & 0x7;
+                let v = self.get_r8(mmu, idx);
+                let r = self.dec8(v);
+                self.set_r8(mmu, idx, r);
+                if idx == 6 { 12 } else { 4 }
+            }
+
+            // INC/DEC r16
+            0x03 => { self.r.set_bc(self.r.bc().wrapping_add(1)); 8 }
+            0x13 => { self.r.set_de(self.r.de().wrapping_add(1)); 8 }
+            0x23 => { self.r.set_hl(self.r.hl().wrapping_add(1)); 8 }
+            0x33 => { self.r.sp = self.r.sp.wrapping_add(1); 8 }
+            0x0b => { self.r.set_bc(self.r.bc().wrapping_sub(1)); 8 }
+            0x1b => { self.r.set_de(self.r.de().wrapping_sub(1)); 8 }
+            0x2b => { self.r.set_hl(self.r.hl().wrapping_sub(1)); 8 }
+            0x3b => { self.r.sp = self.r.sp.wrapping_sub(1); 8 }
+
+            // ADD HL, r16
+            0x09 => { let v = self.add16(self.r.hl(), self.r.bc()); self.r.set_hl(v); 8 }
+            0x19 => { let v = self.add16(self.r.hl(), self.r.de()); self.r.set_hl(v); 8 }
+            0x29 => { let v = self.add16(self.r.hl(), self.r.hl()); self.r.set_hl(v); 8 }
+            0x39 => { let v = self.add16(self.r.hl(), self.r.sp); self.r.set_hl(v); 8 }
+
+            // ADD SP, r8
+            0xe8 => {
+                let off = self.fetch(mmu) as i8 as i16;
+                let sp = self.r.sp;
+                self.r.f = 0;
+                self.set_flag(FLAG_H, (sp & 0xf) as i16 + (off & 0xf) > 0xf);
+                self.set_flag(FLAG_C, (sp & 0xff) as i16 + (off & 0xff) > 0xff);
+                self.r.sp = sp.wrapping_add(off as u16);
+                16
+            }
+
+            // Rotate A (non-CB, always clears Z)
+            0x07 => { let v = self.r.a; let c = v >> 7; self.r.a = v.rotate_left(1); self.r.f = if c != 0 { FLAG_C } else { 0 }; 4 }
+            0x0f => { let v = self.r.a; let c = v & 1; self.r.a = v.rotate_right(1); self.r.f = if c != 0 { FLAG_C } else { 0 }; 4 }
+            0x17 => {
+                let v = self.r.a; let old_c = self.flag(FLAG_C) as u8;
+                self.r.a = (v << 1) | old_c;
+                self.r.f = if v & 0x80 != 0 { FLAG_C } else { 0 };
+                4
+            }
+            0x1f => {
+                let v = self.r.a; let old_c = self.flag(FLAG_C) as u8;
+                self.r.a = (v >> 1) | (old_c << 7);
+                self.r.f = if v & 1 != 0 { FLAG_C } else { 0 };
+                4
+            }
+
+            0x27 => { self.daa(); 4 }
+            0x2f => { self.r.a = !self.r.a; self.set_flag(FLAG_N, true); self.set_flag(FLAG_H, true); 4 }
+            0x37 => { self.set_flag(FLAG_N, false); self.set_flag(FLAG_H, false); self.set_flag(FLAG_C, true); 4 }
+            0x3f => { self.set_flag(FLAG_N, false); self.set_flag(FLAG_H, false); let c = self.flag(FLAG_C); self.set_flag(FLAG_C, !c); 4 }
+
+            // JR
+            0x18 => { let off = self.fetch(mmu) as i8; self.r.pc = self.r.pc.wrapping_add(off as u16); 12 }
+            0x20 | 0x28 | 0x30 | 0x38 => {
+                let off = self.fetch(mmu) as i8;
+                let idx = (op >> 3) & 0x3;
+                if self.cond(idx) {
+                    self.r.pc = self.r.pc.wrapping_add(off as u16);
+                    12
+                } else {
+                    8
+                }
+            }
+
+            // JP
+            0xc3 => { self.r.pc = self.fetch16(mmu); 16 }
+            0xe9 => { self.r.pc = self.r.hl(); 4 }
+            0xc2 | 0xca | 0xd2 | 0xda => {
+                let addr = self.fetch16(mmu);
+                let idx = (op >> 3) & 0x3;
+                if self.cond(idx) { self.r.pc = addr; 16 } else { 12 }
+            }
+
+            // CALL / RET / RST
+            0xcd => { let addr = self.fetch16(mmu); self.push(mmu, self.r.pc); self.r.pc = addr; 24 }
+            0xc4 | 0xcc | 0xd4 | 0xdc => {
+                let addr = self.fetch16(mmu);
+                let idx = (op >> 3) & 0x3;
+                if self.cond(idx) { self.push(mmu, self.r.pc); self.r.pc = addr; 24 } else { 12 }
+            }
+            0xc9 => { self.r.pc = self.pop(mmu); 16 }
+            0xd9 => { self.r.pc = self.pop(mmu); self.ime = true; 16 }
+            0xc0 | 0xc8 | 0xd0 | 0xd8 => {
+                let idx = (op >> 3) & 0x3;
+                if self.cond(idx) { self.r.pc = self.pop(mmu); 20 } else { 8 }
+            }
+            0xc7 | 0xcf | 0xd7 | 0xdf | 0xe7 | 0xef | 0xf7 | 0xff => {
+                self.push(mmu, self.r.pc);
+                self.r.pc = (op & 0x38) as u16;
+                16
+            }
+
+            // PUSH/POP
+            0xc5 => { self.push(mmu, self.r.bc()); 16 }
+            0xd5 => { self.push(mmu, self.r.de()); 16 }
+            0xe5 => { self.push(mmu, self.r.hl()); 16 }
+            0xf5 => { self.push(mmu, self.r.af()); 16 }
+            0xc1 => { let v = self.pop(mmu); self.r.set_bc(v); 12 }
+            0xd1 => { let v = self.pop(mmu); self.r.set_de(v); 12 }
+            0xe1 => { let v = self.pop(mmu); self.r.set_hl(v); 12 }
+            0xf1 => { let v = self.pop(mmu); self.r.set_af(v); 12 }
+
+            // ALU A, d8
+            0xc6 => { let v = self.fetch(mmu); self.r.a = self.add8(self.r.a, v, 0); 8 }
+            0xce => { let v = self.fetch(mmu); let c = self.flag(FLAG_C) as u8; self.r.a = self.add8(self.r.a, v, c); 8 }
+            0xd6 => { let v = self.fetch(mmu); self.r.a = self.sub8(self.r.a, v, 0); 8 }
+            0xde => { let v = self.fetch(mmu); let c = self.flag(FLAG_C) as u8; self.r.a = self.sub8(self.r.a, v, c); 8 }
+            0xe6 => { let v = self.fetch(mmu); self.r.a = self.and8(self.r.a, v); 8 }
+            0xee => { let v = self.fetch(mmu); self.r.a = self.xor8(self.r.a, v); 8 }
+            0xf6 => { let v = self.fetch(mmu); self.r.a = self.or8(self.r.a, v); 8 }
+            0xfe => { let v = self.fetch(mmu); self.sub8(self.r.a, v, 0); 8 }
+
+            0xcb => { let op2 = self.fetch(mmu); self.execute_cb(mmu, op2) }
+
+            // Remaining 0x40-0xBF block: LD r,r' / ALU A,r / HALT handled above
+            _ if (0x40..=0xbf).contains(&op) => self.execute_block(mmu, op),
+
+            _ => 4, // unimplemented/illegal opcode: treat as a NOP-length stall
+        }
+    }
+
+    /** The regular 8x8 grid of `LD r, r'` and `ALU A, r` opcodes, which
+     * decode cleanly from the bit pattern `01dddsss` / `10aaasss`. */
+    fn execute_block<S: SaveStore>(&mut self, mmu: &mut Mmu<S>, op: u8) -> u32 {
+        let src = op & 0x7;
+        let is_hl = src == 6;
+        let base_cycles = if is_hl { 8 } else { 4 };
+
+        if (0x40..=0x7f).contains(&op) {
+            let dst = (op >> 3) & 0x7;
+            let val = self.get_r8(mmu, src);
+            self.set_r8(mmu, dst, val);
+            return if is_hl || dst == 6 { 8 } else { 4 };
+        }
+
+        let val = self.get_r8(mmu, src);
+        let carry = self.flag(FLAG_C) as u8;
+        match (op >> 3) & 0x7 {
+            0 => self.r.a = self.add8(self.r.a, val, 0),
+            1 => self.r.a = self.add8(self.r.a, val, carry),
+            2 => self.r.a = self.sub8(self.r.a, val, 0),
+            3 => self.r.a = self.sub8(self.r.a, val, carry),
+            4 => self.r.a = self.and8(self.r.a, val),
+            5 => self.r.a = self.xor8(self.r.a, val),
+            6 => self.r.a = self.or8(self.r.a, val),
+            _ => { self.sub8(self.r.a, val, 0); }
+        }
+        base_cycles
+    }
+
+    fn daa(&mut self) {
+        let mut a = self.r.a;
+        let mut adjust = 0u8;
+        let mut carry = self.flag(FLAG_C);
+        if self.flag(FLAG_H) || (!self.flag(FLAG_N) && (a & 0xf) > 9) {
+            adjust |= 0x06;
+        }
+        if carry || (!self.flag(FLAG_N) && a > 0x99) {
+            adjust |= 0x60;
+            carry = true;
+        }
+        a = if self.flag(FLAG_N) { a.wrapping_sub(adjust) } else { a.wrapping_add(adjust) };
+        self.r.a = a;
+        self.set_flag(FLAG_Z, a == 0);
+        self.set_flag(FLAG_H, false);
+        self.set_flag(FLAG_C, carry);
+    }
+
+    /** The CB-prefixed table: rotate/shift/swap, then BIT/RES/SET, each
+     * operating on one of the eight `r8` operands. */
+    fn execute_cb<S: SaveStore>(&mut self, mmu: &mut Mmu<S>, op: u8) -> u32 {
+        let reg = op & 0x7;
+        let is_hl = reg == 6;
+        let v = self.get_r8(mmu, reg);
+        let bit = (op >> 3) & 0x7;
+
+        let result = match op >> 6 {
+            0 => {
+                let r = match bit {
+                    0 => { let c = v >> 7; let r = v.rotate_left(1); self.set_flag(FLAG_C, c != 0); r }
+                    1 => { let c = v & 1; let r = v.rotate_right(1); self.set_flag(FLAG_C, c != 0); r }
+                    2 => { let old_c = self.flag(FLAG_C) as u8; let r = (v << 1) | old_c; self.set_flag(FLAG_C, v & 0x80 != 0); r }
+                    3 => { let old_c = self.flag(FLAG_C) as u8; let r = (v >> 1) | (old_c << 7); self.set_flag(FLAG_C, v & 1 != 0); r }
+                    4 => { let c = v >> 7; let r = v << 1; self.set_flag(FLAG_C, c != 0); r }
+                    5 => { let c = v & 1; let r = ((v as i8) >> 1) as u8; self.set_flag(FLAG_C, c != 0); r }
+                    6 => { self.r.f = 0; v.rotate_left(4) }
+                    _ => { let c = v & 1; let r = v >> 1; self.set_flag(FLAG_C, c != 0); r }
+                };
+                self.set_flag(FLAG_Z, r == 0);
+                self.set_flag(FLAG_N, false);
+                if bit != 6 {
+                    self.set_flag(FLAG_H, false);
+                }
+                Some(r)
+            }
+            1 => {
+                // BIT b, r: leaves r unchanged.
+                self.set_flag(FLAG_Z, v & (1 << bit) == 0);
+                self.set_flag(FLAG_N, false);
+                self.set_flag(FLAG_H, true);
+                None
+            }
+            2 => Some(v & !(1 << bit)), // RES b, r
+            _ => Some(v | (1 << bit)),  // SET b, r
+        };
+
+        if let Some(r) = result {
+            self.set_r8(mmu, reg, r);
+        }
+        if is_hl {
+            if op >> 6 == 1 { 12 } else { 16 }
+        } else {
+            8
+        }
+    }
+}
@@ -0,0 +1,64 @@
+//! Joypad register at `0xFF00`.
+
+/** The eight buttons on a DMG, as exposed through the two nibbles of
+ * the joypad register. */
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum Button {
+    Right,
+    Left,
+    Up,
+    Down,
+    A,
+    B,
+    Select,
+    Start,
+}
+
+/** Tracks button state and renders it through whichever of the two
+ * select lines (`P14`/`P15`) the game has asked for. */
+pub struct Joypad {
+    buttons: u8, // bit set = pressed, indexed as Button as u8
+    select: u8,  // raw bits 4-5 as last written
+}
+
+impl Joypad {
+    pub fn new() -> Self {
+        Self { buttons: 0, select: 0x30 }
+    }
+
+    pub fn set(&mut self, button: Button, pressed: bool) {
+        let bit = 1 << (button as u8);
+        if pressed {
+            self.buttons |= bit;
+        } else {
+            self.buttons &= !bit;
+        }
+    }
+
+    pub fn write(&mut self, val: u8) {
+        self.select = val & 0x30;
+    }
+
+    /** Builds the `0xFF00` register value from the select lines and
+     * whichever group of buttons they choose; active-low, so a 0 bit
+     * means pressed. Requesting the joypad interrupt on a button press
+     * is the caller's job, not this method's. */
+    pub fn read(&self) -> u8 {
+        let mut lines = 0x0f;
+        if self.select & 0x10 == 0 {
+            // P14 low selects the direction keys
+            if self.buttons & (1 << Button::Right as u8) != 0 { lines &= !0x1; }
+            if self.buttons & (1 << Button::Left as u8) != 0 { lines &= !0x2; }
+            if self.buttons & (1 << Button::Up as u8) != 0 { lines &= !0x4; }
+            if self.buttons & (1 << Button::Down as u8) != 0 { lines &= !0x8; }
+        }
+        if self.select & 0x20 == 0 {
+            // P15 low selects the action buttons
+            if self.buttons & (1 << Button::A as u8) != 0 { lines &= !0x1; }
+            if self.buttons & (1 << Button::B as u8) != 0 { lines &= !0x2; }
+            if self.buttons & (1 << Button::Select as u8) != 0 { lines &= !0x4; }
+            if self.buttons & (1 << Button::Start as u8) != 0 { lines &= !0x8; }
+        }
+        0xc0 | self.select | lines
+    }
+}
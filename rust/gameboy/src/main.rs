@@ -0,0 +1,276 @@
+#![allow(dead_code)]
+#![allow(non_snake_case)]
+#![allow(non_camel_case_types)]
+#![no_std]
+#![no_main]
+
+mod apu;
+mod cart;
+mod cpu;
+mod joypad;
+mod mmu;
+mod ppu;
+mod save;
+mod timer;
+
+use k210_hal::pac;
+use k210_hal::prelude::*;
+use k210_hal::stdout::Stdout;
+use k210_shared::board::def::{io,DISP_WIDTH,DISP_HEIGHT,NS2009_SLV_ADDR,NS2009_CAL,NS2009_ADDR_BITS,NS2009_CLK};
+use k210_shared::board::lcd::{LCD,self};
+use k210_shared::board::lcd_colors;
+use k210_shared::board::ns2009::TouchScreen;
+use k210_shared::soc::fpioa;
+use k210_shared::soc::i2c::{I2C,I2CExt};
+use k210_shared::soc::i2s::{I2S,I2SExt};
+use k210_shared::soc::sdcard::{SDCard,SDCardExt};
+use k210_shared::soc::sleep::usleep;
+use k210_shared::soc::spi::SPIExt;
+use k210_shared::soc::sysctl;
+use riscv_rt::entry;
+
+use apu::SAMPLES_PER_SECOND;
+use cart::Cart;
+use cpu::Cpu;
+use joypad::Button;
+use mmu::Mmu;
+use ppu::{SCREEN_WIDTH, SCREEN_HEIGHT};
+use save::{NullStore, SaveStore};
+
+/** Persists cartridge RAM to a fixed range of SD card sectors, one
+ * 512-byte sector per save slot, rounding the save up to whole
+ * sectors. Good enough for the 8-32 KiB save sizes MBC1/MBC3 carts
+ * use. */
+struct SdSaveStore {
+    sd: SDCard,
+}
+
+const SAVE_BASE_SECTOR: u32 = 0x10000;
+
+impl SaveStore for SdSaveStore {
+    fn load(&mut self, slot: u32, out: &mut [u8]) -> bool {
+        let sectors = (out.len() + 511) / 512;
+        self.sd.read_sectors(SAVE_BASE_SECTOR + slot * 64, sectors as u32, out)
+    }
+
+    fn save(&mut self, slot: u32, data: &[u8]) {
+        let sectors = (data.len() + 511) / 512;
+        self.sd.write_sectors(SAVE_BASE_SECTOR + slot * 64, sectors as u32, data);
+    }
+}
+
+/** The save backend, chosen once at boot. `Cart`/`Mmu` are generic
+ * over `SaveStore` and monomorphize to a single concrete type, so the
+ * SD-present and SD-absent cases have to share this one enum rather
+ * than branching to two different concrete stores. */
+enum Store {
+    Sd(SdSaveStore),
+    Null(NullStore),
+}
+
+impl SaveStore for Store {
+    fn load(&mut self, slot: u32, out: &mut [u8]) -> bool {
+        match self {
+            Store::Sd(sd) => sd.load(slot, out),
+            Store::Null(null) => null.load(slot, out),
+        }
+    }
+
+    fn save(&mut self, slot: u32, data: &[u8]) {
+        match self {
+            Store::Sd(sd) => sd.save(slot, data),
+            Store::Null(null) => null.save(slot, data),
+        }
+    }
+}
+
+/** Game Boy CPU clock, used to convert elapsed T-cycles into audio
+ * samples at `SAMPLES_PER_SECOND`. */
+const CPU_HZ: u32 = 4_194_304;
+
+/** Samples pulled from the APU and pushed to the I2S TX ring buffer
+ * each time we've accumulated enough CPU cycles for a full chunk. */
+const AUDIO_CHUNK_FRAMES: usize = 256;
+
+/** Array for representing an image of the entire screen, see
+ * `game-of-life` for the packing convention. */
+pub type ScreenImage = [u32; DISP_WIDTH * DISP_HEIGHT / 2];
+
+/** The cartridge image. The checked-in `rom.gb` is an intentionally
+ * minimal placeholder: a valid, unbanked 32 KiB header whose entry
+ * point just spins forever, so the emulator boots cleanly out of the
+ * box. Swap it out for the ROM you actually want to run. */
+static ROM: &[u8] = include_bytes!("../rom.gb");
+
+/** RGB565 palette for the four DMG shades, lightest to darkest. */
+const PALETTE: [u16; 4] = [0xe7fc, 0x8e13, 0x4a49, 0x0841];
+
+/** Connect pins to internal functions */
+fn io_mux_init() {
+    fpioa::set_function(io::LCD_RST.into(), fpioa::function::gpiohs(lcd::RST_GPIONUM));
+    fpioa::set_io_pull(io::LCD_RST.into(), fpioa::pull::DOWN);
+    fpioa::set_function(io::LCD_DC.into(), fpioa::function::gpiohs(lcd::DCX_GPIONUM));
+    fpioa::set_io_pull(io::LCD_DC.into(), fpioa::pull::DOWN);
+    fpioa::set_function(io::LCD_CS.into(), fpioa::function::SPI0_SS3);
+    fpioa::set_function(io::LCD_WR.into(), fpioa::function::SPI0_SCLK);
+
+    fpioa::set_function(io::I2C1_SCL.into(), fpioa::function::I2C0_SCLK);
+    fpioa::set_function(io::I2C1_SDA.into(), fpioa::function::I2C0_SDA);
+
+    /* I2S0 for DAC/speaker audio out */
+    fpioa::set_function(io::I2S0_MCLK.into(), fpioa::function::I2S0_MCLK);
+    fpioa::set_function(io::I2S0_SCLK.into(), fpioa::function::I2S0_SCLK);
+    fpioa::set_function(io::I2S0_WS.into(), fpioa::function::I2S0_WS);
+    fpioa::set_function(io::I2S0_OUT_D0.into(), fpioa::function::I2S0_OUT_D0);
+
+    sysctl::set_spi0_dvp_data(true);
+}
+
+/** Set correct voltage for pins */
+fn io_set_power() {
+    sysctl::set_power_mode(sysctl::power_bank::BANK6, sysctl::io_power_mode::V18);
+    sysctl::set_power_mode(sysctl::power_bank::BANK7, sysctl::io_power_mode::V18);
+}
+
+/** Map a touch-panel press to the Game Boy button it falls under. The
+ * panel is split into a D-pad cross on the left and A/B/Select/Start
+ * on the right, matching a typical handheld layout. */
+fn touch_to_button(x: i32, y: i32) -> Option<Button> {
+    let dpad_cx = DISP_WIDTH as i32 / 4;
+    let dpad_cy = DISP_HEIGHT as i32 * 3 / 4;
+    if x < DISP_WIDTH as i32 / 2 {
+        let dx = x - dpad_cx;
+        let dy = y - dpad_cy;
+        return if dx.abs() < 20 && dy.abs() < 20 {
+            None
+        } else if dx.abs() > dy.abs() {
+            Some(if dx > 0 { Button::Right } else { Button::Left })
+        } else {
+            Some(if dy > 0 { Button::Down } else { Button::Up })
+        };
+    }
+    let qw = DISP_WIDTH as i32 / 4;
+    let col = (x - DISP_WIDTH as i32 / 2) / (qw / 2);
+    Some(match col {
+        0 => Button::Select,
+        1 => Button::Start,
+        2 => Button::B,
+        _ => Button::A,
+    })
+}
+
+/** Convert the GB's 160x144 2-bit-index framebuffer into a centered,
+ * 2x-scaled `ScreenImage` suitable for `lcd.draw_picture`. */
+fn blit(fb: &[u8; SCREEN_WIDTH * SCREEN_HEIGHT], image: &mut ScreenImage) {
+    let off_x = (DISP_WIDTH - SCREEN_WIDTH * 2) / 2;
+    let off_y = (DISP_HEIGHT - SCREEN_HEIGHT * 2) / 2;
+    for y in 0..SCREEN_HEIGHT {
+        for x in 0..SCREEN_WIDTH {
+            let color = PALETTE[fb[y * SCREEN_WIDTH + x] as usize];
+            for dy in 0..2 {
+                let row = off_y + y * 2 + dy;
+                let col = off_x + x * 2;
+                let idx = row * DISP_WIDTH / 2 + col / 2;
+                image[idx] = (color as u32) << 16 | color as u32;
+            }
+        }
+    }
+}
+
+#[entry]
+fn main() -> ! {
+    let p = pac::Peripherals::take().unwrap();
+    let clocks = k210_hal::clock::Clocks::new();
+
+    usleep(200000);
+
+    let serial = p.UARTHS.constrain(115_200.bps(), &clocks);
+    let (mut tx, _) = serial.split();
+    let mut stdout = Stdout(&mut tx);
+
+    io_mux_init();
+    io_set_power();
+
+    let spi = p.SPI0.constrain();
+    let lcd = LCD::new(spi);
+    lcd.init();
+    lcd.set_direction(lcd::direction::YX_LRUD);
+    lcd.clear(lcd_colors::PURPLE);
+
+    let mut image: ScreenImage = [0; DISP_WIDTH * DISP_HEIGHT / 2];
+
+    writeln!(stdout, "NS2009 init").unwrap();
+    let i2c = p.I2C0.constrain();
+    i2c.init(NS2009_SLV_ADDR, NS2009_ADDR_BITS, NS2009_CLK);
+
+    let mut filter = if let Some(filter) = TouchScreen::init(i2c, NS2009_CAL) {
+        filter
+    } else {
+        writeln!(stdout, "NS2009 init failure").unwrap();
+        panic!("Fatal error");
+    };
+
+    writeln!(stdout, "I2S init").unwrap();
+    let i2s = p.I2S0.constrain();
+    i2s.init(SAMPLES_PER_SECOND, 16, 2);
+
+    writeln!(stdout, "SD card init").unwrap();
+    let sd_spi = p.SPI1.constrain();
+    let store = if let Some(sd) = SDCard::init(sd_spi) {
+        Store::Sd(SdSaveStore { sd })
+    } else {
+        // No SD card is fine for ROMs with no battery-backed RAM; just
+        // run without persistence instead of refusing to boot.
+        writeln!(stdout, "SD card init failure, saves disabled").unwrap();
+        Store::Null(NullStore)
+    };
+
+    let cart = Cart::new(ROM, store);
+    let mut mmu = Mmu::new(cart);
+    let mut cpu = Cpu::new();
+
+    let mut audio_cycles = 0u32;
+    let mut audio_buf = [0i16; AUDIO_CHUNK_FRAMES * 2];
+    let mut rtc_cycles = 0u32;
+
+    loop {
+        for button in [Button::Up, Button::Down, Button::Left, Button::Right, Button::A, Button::B, Button::Select, Button::Start] {
+            mmu.joypad.set(button, false);
+        }
+        if let Some(ev) = filter.poll() {
+            if let Some(button) = touch_to_button(ev.x, ev.y) {
+                mmu.joypad.set(button, true);
+                mmu.if_reg |= mmu::INT_JOYPAD;
+            }
+        }
+
+        let cycles = cpu.step(&mut mmu);
+        mmu.step(cycles);
+
+        // The MBC3 RTC isn't wired to a wall clock, so derive its
+        // ticks from elapsed CPU cycles instead: one per CPU_HZ worth
+        // of emulated time, same as real time while the game runs.
+        rtc_cycles += cycles;
+        while rtc_cycles >= CPU_HZ {
+            rtc_cycles -= CPU_HZ;
+            mmu.cart.tick_rtc_second();
+        }
+
+        // Feed the APU a chunk at a time: 1 audio frame every
+        // CPU_HZ / SAMPLES_PER_SECOND CPU cycles, averaged over a
+        // whole chunk so we don't have to track fractional cycles.
+        audio_cycles += cycles;
+        let cycles_per_chunk = CPU_HZ / SAMPLES_PER_SECOND * AUDIO_CHUNK_FRAMES as u32;
+        if audio_cycles >= cycles_per_chunk {
+            audio_cycles -= cycles_per_chunk;
+            mmu.apu.generate(CPU_HZ / SAMPLES_PER_SECOND, &mut audio_buf);
+            i2s.send(&audio_buf);
+        }
+
+        if mmu.ppu.frame_ready {
+            mmu.ppu.frame_ready = false;
+            blit(&mmu.ppu.framebuffer, &mut image);
+            lcd.draw_picture(0, 0, DISP_WIDTH as u16, DISP_HEIGHT as u16, &image);
+        }
+    }
+}
@@ -0,0 +1,140 @@
+//! 64 KiB memory bus: wires cartridge, VRAM/OAM, WRAM, HRAM and the I/O
+//! registers together, and tracks the IF/IE interrupt flags.
+
+use crate::apu::Apu;
+use crate::cart::Cart;
+use crate::joypad::Joypad;
+use crate::ppu::Ppu;
+use crate::save::SaveStore;
+use crate::timer::Timer;
+
+pub const INT_VBLANK: u8 = 0x01;
+pub const INT_STAT: u8 = 0x02;
+pub const INT_TIMER: u8 = 0x04;
+pub const INT_SERIAL: u8 = 0x08;
+pub const INT_JOYPAD: u8 = 0x10;
+
+pub struct Mmu<'a, S: SaveStore> {
+    pub cart: Cart<'a, S>,
+    pub ppu: Ppu,
+    pub timer: Timer,
+    pub joypad: Joypad,
+    pub apu: Apu,
+    wram: [u8; 0x2000],
+    hram: [u8; 0x7f],
+    pub if_reg: u8,
+    pub ie_reg: u8,
+    serial_data: u8,
+    serial_control: u8,
+}
+
+impl<'a, S: SaveStore> Mmu<'a, S> {
+    pub fn new(cart: Cart<'a, S>) -> Self {
+        Self {
+            cart,
+            ppu: Ppu::new(),
+            timer: Timer::new(),
+            joypad: Joypad::new(),
+            apu: Apu::new(),
+            wram: [0; 0x2000],
+            hram: [0; 0x7f],
+            if_reg: 0xe1,
+            ie_reg: 0,
+            serial_data: 0,
+            serial_control: 0,
+        }
+    }
+
+    pub fn read(&self, addr: u16) -> u8 {
+        match addr {
+            0x0000..=0x7fff => self.cart.read(addr),
+            0x8000..=0x9fff => self.ppu.read_vram(addr),
+            0xa000..=0xbfff => self.cart.read(addr),
+            0xc000..=0xdfff => self.wram[(addr - 0xc000) as usize],
+            0xe000..=0xfdff => self.wram[(addr - 0xe000) as usize],
+            0xfe00..=0xfe9f => self.ppu.read_oam(addr - 0xfe00),
+            0xfea0..=0xfeff => 0xff,
+            0xff00 => self.joypad.read(),
+            0xff01 => self.serial_data,
+            0xff02 => self.serial_control,
+            0xff04..=0xff07 => self.timer.read(addr),
+            0xff0f => self.if_reg | 0xe0,
+            0xff10..=0xff26 | 0xff30..=0xff3f => self.apu.read_register(addr),
+            0xff40 => self.ppu.lcdc,
+            0xff41 => self.ppu.stat | 0x80,
+            0xff42 => self.ppu.scy,
+            0xff43 => self.ppu.scx,
+            0xff44 => self.ppu.ly,
+            0xff45 => self.ppu.lyc,
+            0xff47 => self.ppu.bgp,
+            0xff48 => self.ppu.obp0,
+            0xff49 => self.ppu.obp1,
+            0xff4a => self.ppu.wy,
+            0xff4b => self.ppu.wx,
+            0xff80..=0xfffe => self.hram[(addr - 0xff80) as usize],
+            0xffff => self.ie_reg,
+            _ => 0xff,
+        }
+    }
+
+    pub fn write(&mut self, addr: u16, val: u8) {
+        match addr {
+            0x0000..=0x7fff => self.cart.write(addr, val),
+            0x8000..=0x9fff => self.ppu.write_vram(addr, val),
+            0xa000..=0xbfff => self.cart.write(addr, val),
+            0xc000..=0xdfff => self.wram[(addr - 0xc000) as usize] = val,
+            0xe000..=0xfdff => self.wram[(addr - 0xe000) as usize] = val,
+            0xfe00..=0xfe9f => self.ppu.write_oam(addr - 0xfe00, val),
+            0xfea0..=0xfeff => {}
+            0xff00 => self.joypad.write(val),
+            0xff01 => self.serial_data = val,
+            0xff02 => self.serial_control = val,
+            0xff04..=0xff07 => self.timer.write(addr, val),
+            0xff0f => self.if_reg = val & 0x1f,
+            0xff10..=0xff26 | 0xff30..=0xff3f => self.apu.push_register(addr, val),
+            0xff40 => self.ppu.lcdc = val,
+            0xff41 => self.ppu.stat = (self.ppu.stat & 0x3) | (val & !0x3),
+            0xff42 => self.ppu.scy = val,
+            0xff43 => self.ppu.scx = val,
+            0xff45 => self.ppu.lyc = val,
+            0xff46 => self.dma(val),
+            0xff47 => self.ppu.bgp = val,
+            0xff48 => self.ppu.obp0 = val,
+            0xff49 => self.ppu.obp1 = val,
+            0xff4a => self.ppu.wy = val,
+            0xff4b => self.ppu.wx = val,
+            0xff80..=0xfffe => self.hram[(addr - 0xff80) as usize] = val,
+            0xffff => self.ie_reg = val,
+            _ => {}
+        }
+    }
+
+    pub fn read16(&self, addr: u16) -> u16 {
+        self.read(addr) as u16 | (self.read(addr.wrapping_add(1)) as u16) << 8
+    }
+
+    pub fn write16(&mut self, addr: u16, val: u16) {
+        self.write(addr, val as u8);
+        self.write(addr.wrapping_add(1), (val >> 8) as u8);
+    }
+
+    /** OAM DMA: copies 160 bytes from `val << 8` into OAM in one go;
+     * real hardware takes 160 cycles and blocks most other bus access,
+     * which we don't model yet. */
+    fn dma(&mut self, val: u8) {
+        let src = (val as u16) << 8;
+        for i in 0..0xa0u16 {
+            let byte = self.read(src + i);
+            self.ppu.write_oam(i, byte);
+        }
+    }
+
+    /** Step timer and PPU by `cycles` and latch any interrupts they
+     * raised into IF. */
+    pub fn step(&mut self, cycles: u32) {
+        if self.timer.step(cycles) {
+            self.if_reg |= INT_TIMER;
+        }
+        self.if_reg |= self.ppu.step(cycles);
+    }
+}
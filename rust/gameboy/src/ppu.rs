@@ -0,0 +1,268 @@
+//! Picture Processing Unit: VRAM/OAM scanout and STAT/LY timing.
+
+pub const SCREEN_WIDTH: usize = 160;
+pub const SCREEN_HEIGHT: usize = 144;
+
+const VRAM_SIZE: usize = 0x2000;
+const OAM_SIZE: usize = 0xa0;
+
+const MODE_HBLANK: u8 = 0;
+const MODE_VBLANK: u8 = 1;
+const MODE_OAM: u8 = 2;
+const MODE_TRANSFER: u8 = 3;
+
+/** The standard mode cycle: OAM scan (80 cycles) -> pixel transfer (172
+ * cycles) -> HBlank (204 cycles), repeated for 144 visible lines,
+ * followed by ten VBlank lines. */
+pub struct Ppu {
+    vram: [u8; VRAM_SIZE],
+    oam: [u8; OAM_SIZE],
+    pub lcdc: u8,
+    pub stat: u8,
+    pub scy: u8,
+    pub scx: u8,
+    pub ly: u8,
+    pub lyc: u8,
+    pub wy: u8,
+    pub wx: u8,
+    pub bgp: u8,
+    pub obp0: u8,
+    pub obp1: u8,
+    dot: u32,
+    /** 2-bit colour indices, one byte per pixel, row-major. */
+    pub framebuffer: [u8; SCREEN_WIDTH * SCREEN_HEIGHT],
+    pub frame_ready: bool,
+}
+
+impl Ppu {
+    pub fn new() -> Self {
+        Self {
+            vram: [0; VRAM_SIZE],
+            oam: [0; OAM_SIZE],
+            lcdc: 0x91,
+            stat: MODE_OAM,
+            scy: 0,
+            scx: 0,
+            ly: 0,
+            lyc: 0,
+            wy: 0,
+            wx: 0,
+            bgp: 0xfc,
+            obp0: 0xff,
+            obp1: 0xff,
+            dot: 0,
+            framebuffer: [0; SCREEN_WIDTH * SCREEN_HEIGHT],
+            frame_ready: false,
+        }
+    }
+
+    pub fn read_vram(&self, addr: u16) -> u8 {
+        self.vram[(addr & 0x1fff) as usize]
+    }
+
+    pub fn write_vram(&mut self, addr: u16, val: u8) {
+        self.vram[(addr & 0x1fff) as usize] = val;
+    }
+
+    pub fn read_oam(&self, addr: u16) -> u8 {
+        self.oam[(addr as usize) & 0xff]
+    }
+
+    pub fn write_oam(&mut self, addr: u16, val: u8) {
+        let idx = addr as usize;
+        if idx < OAM_SIZE {
+            self.oam[idx] = val;
+        }
+    }
+
+    fn mode(&self) -> u8 {
+        self.stat & 0x3
+    }
+
+    fn set_mode(&mut self, mode: u8) {
+        self.stat = (self.stat & !0x3) | mode;
+    }
+
+    /** Advance PPU timing by `cycles` and return the bits of IF that
+     * should be set this step (VBlank and/or STAT). */
+    pub fn step(&mut self, cycles: u32) -> u8 {
+        if self.lcdc & 0x80 == 0 {
+            return 0;
+        }
+        let mut irq = 0u8;
+        self.dot += cycles;
+
+        match self.mode() {
+            MODE_OAM if self.dot >= 80 => {
+                self.dot -= 80;
+                self.set_mode(MODE_TRANSFER);
+            }
+            MODE_TRANSFER if self.dot >= 172 => {
+                self.dot -= 172;
+                self.set_mode(MODE_HBLANK);
+                self.render_line();
+                if self.stat & 0x08 != 0 {
+                    irq |= 0x2; // STAT
+                }
+            }
+            MODE_HBLANK if self.dot >= 204 => {
+                self.dot -= 204;
+                self.ly += 1;
+                if self.ly == SCREEN_HEIGHT as u8 {
+                    self.set_mode(MODE_VBLANK);
+                    self.frame_ready = true;
+                    irq |= 0x1; // VBlank
+                    if self.stat & 0x10 != 0 {
+                        irq |= 0x2;
+                    }
+                } else {
+                    self.set_mode(MODE_OAM);
+                    if self.stat & 0x20 != 0 {
+                        irq |= 0x2;
+                    }
+                }
+                irq |= self.check_lyc();
+            }
+            MODE_VBLANK if self.dot >= 456 => {
+                self.dot -= 456;
+                self.ly += 1;
+                if self.ly > 153 {
+                    self.ly = 0;
+                    self.set_mode(MODE_OAM);
+                    if self.stat & 0x20 != 0 {
+                        irq |= 0x2;
+                    }
+                }
+                irq |= self.check_lyc();
+            }
+            _ => {}
+        }
+        irq
+    }
+
+    fn check_lyc(&mut self) -> u8 {
+        if self.ly == self.lyc {
+            self.stat |= 0x04;
+            if self.stat & 0x40 != 0 {
+                return 0x2;
+            }
+        } else {
+            self.stat &= !0x04;
+        }
+        0
+    }
+
+    fn bg_tile_data(&self, tile_idx: u8, row: u8) -> (u8, u8) {
+        let base: u16 = if self.lcdc & 0x10 != 0 {
+            0x8000 + (tile_idx as u16) * 16
+        } else {
+            0x9000u16.wrapping_add((tile_idx as i8 as i16 as u16).wrapping_mul(16))
+        };
+        let addr = base + (row as u16) * 2;
+        (self.read_vram(addr - 0x8000), self.read_vram(addr + 1 - 0x8000))
+    }
+
+    /** Render one scanline of background, window and sprites into the
+     * framebuffer at `self.ly`. */
+    fn render_line(&mut self) {
+        let y = self.ly as usize;
+        if y >= SCREEN_HEIGHT {
+            return;
+        }
+        let mut bg_index = [0u8; SCREEN_WIDTH];
+
+        if self.lcdc & 0x01 != 0 {
+            let map_base: u16 = if self.lcdc & 0x08 != 0 { 0x9c00 } else { 0x9800 };
+            let py = self.scy.wrapping_add(self.ly);
+            for x in 0..SCREEN_WIDTH {
+                let px = self.scx.wrapping_add(x as u8);
+                let tile_col = (px / 8) as u16;
+                let tile_row = (py / 8) as u16;
+                let tile_idx = self.read_vram(map_base - 0x8000 + tile_row * 32 + tile_col);
+                let (lo, hi) = self.bg_tile_data(tile_idx, py % 8);
+                let bit = 7 - (px % 8);
+                let color = ((hi >> bit) & 1) << 1 | ((lo >> bit) & 1);
+                bg_index[x] = color;
+                self.framebuffer[y * SCREEN_WIDTH + x] = (self.bgp >> (color * 2)) & 0x3;
+            }
+        }
+
+        if self.lcdc & 0x20 != 0 && self.ly >= self.wy {
+            let map_base: u16 = if self.lcdc & 0x40 != 0 { 0x9c00 } else { 0x9800 };
+            let wy_line = self.ly - self.wy;
+            for x in 0..SCREEN_WIDTH {
+                let wx = self.wx as i16 - 7;
+                if (x as i16) < wx {
+                    continue;
+                }
+                let wpx = (x as i16 - wx) as u8;
+                let tile_col = (wpx / 8) as u16;
+                let tile_row = (wy_line / 8) as u16;
+                let tile_idx = self.read_vram(map_base - 0x8000 + tile_row * 32 + tile_col);
+                let (lo, hi) = self.bg_tile_data(tile_idx, wy_line % 8);
+                let bit = 7 - (wpx % 8);
+                let color = ((hi >> bit) & 1) << 1 | ((lo >> bit) & 1);
+                bg_index[x] = color;
+                self.framebuffer[y * SCREEN_WIDTH + x] = (self.bgp >> (color * 2)) & 0x3;
+            }
+        }
+
+        if self.lcdc & 0x02 != 0 {
+            self.render_sprites(y, &bg_index);
+        }
+    }
+
+    fn render_sprites(&mut self, y: usize, bg_index: &[u8; SCREEN_WIDTH]) {
+        let tall = self.lcdc & 0x04 != 0;
+        let height = if tall { 16 } else { 8 };
+        let mut drawn = 0;
+        // DMG sprite priority: lower X wins, OAM index breaks ties. We
+        // walk OAM in index order and track the X of whichever sprite
+        // already claimed each pixel, so only a strictly-lower-X sprite
+        // (or, on equal X, whichever got there first) can overwrite it.
+        let mut winner_x = [i16::MAX; SCREEN_WIDTH];
+        for sprite in 0..40 {
+            if drawn >= 10 {
+                break;
+            }
+            let base = sprite * 4;
+            let sy = self.oam[base] as i16 - 16;
+            let sx = self.oam[base + 1] as i16 - 8;
+            let mut tile = self.oam[base + 2];
+            let attr = self.oam[base + 3];
+            let row = self.ly as i16 - sy;
+            if row < 0 || row >= height {
+                continue;
+            }
+            drawn += 1;
+            let row = if attr & 0x40 != 0 { height - 1 - row } else { row };
+            if tall {
+                tile &= 0xfe;
+            }
+            let tile = tile as u16 + (row / 8) as u16;
+            let addr = tile * 16 + ((row % 8) as u16) * 2;
+            let lo = self.read_vram(addr);
+            let hi = self.read_vram(addr + 1);
+            let palette = if attr & 0x10 != 0 { self.obp1 } else { self.obp0 };
+            for px in 0..8i16 {
+                let sx_pix = sx + px;
+                if sx_pix < 0 || sx_pix as usize >= SCREEN_WIDTH {
+                    continue;
+                }
+                let bit = if attr & 0x20 != 0 { px } else { 7 - px };
+                let color = ((hi >> bit) & 1) << 1 | ((lo >> bit) & 1);
+                if color == 0 {
+                    continue;
+                }
+                if attr & 0x80 != 0 && bg_index[sx_pix as usize] != 0 {
+                    continue; // behind background
+                }
+                if sx >= winner_x[sx_pix as usize] {
+                    continue; // a higher-priority sprite already drew here
+                }
+                winner_x[sx_pix as usize] = sx;
+                self.framebuffer[y * SCREEN_WIDTH + sx_pix as usize] = (palette >> (color * 2)) & 0x3;
+            }
+        }
+    }
+}
@@ -0,0 +1,29 @@
+//! Persistence hook for battery-backed cartridge RAM.
+//!
+//! The K210 boards this SDK targets expose both SPI flash and an SD
+//! slot; either can back a save file, so the cartridge mapper is
+//! generic over this trait rather than hard-coding one.
+
+/** A place to persist a cartridge's external RAM banks across power
+ * cycles. `slot` identifies the save file/sector so multiple ROMs (or
+ * multiple RAM banks) don't collide. */
+pub trait SaveStore {
+    /** Fill `out` from the backing store for `slot`, returning `false`
+     * if there was nothing saved yet (leaving `out` untouched). */
+    fn load(&mut self, slot: u32, out: &mut [u8]) -> bool;
+
+    /** Persist `data` for `slot`. */
+    fn save(&mut self, slot: u32, data: &[u8]);
+}
+
+/** A `SaveStore` that discards everything; used when a cartridge has
+ * no battery-backed RAM, or no storage backend was wired up. */
+pub struct NullStore;
+
+impl SaveStore for NullStore {
+    fn load(&mut self, _slot: u32, _out: &mut [u8]) -> bool {
+        false
+    }
+
+    fn save(&mut self, _slot: u32, _data: &[u8]) {}
+}
@@ -0,0 +1,73 @@
+//! DIV/TIMA timer at `0xFF04`-`0xFF07`.
+
+/** Counts the system clock into DIV and, when enabled by TAC, into TIMA,
+ * raising the timer interrupt on overflow. */
+pub struct Timer {
+    div: u16,
+    tima: u8,
+    tma: u8,
+    tac: u8,
+    overflowed: bool,
+}
+
+impl Timer {
+    pub fn new() -> Self {
+        Self {
+            div: 0,
+            tima: 0,
+            tma: 0,
+            tac: 0,
+            overflowed: false,
+        }
+    }
+
+    fn tima_bit(&self) -> u16 {
+        match self.tac & 0x3 {
+            0 => 9,
+            1 => 3,
+            2 => 5,
+            _ => 7,
+        }
+    }
+
+    /** Advance the timer by `cycles` CPU clocks, returning true if TIMA
+     * overflowed and the timer interrupt should be requested. */
+    pub fn step(&mut self, cycles: u32) -> bool {
+        let mut fired = false;
+        for _ in 0..cycles {
+            let before = (self.div >> self.tima_bit()) & 1 != 0 && self.tac & 0x4 != 0;
+            self.div = self.div.wrapping_add(1);
+            let after = (self.div >> self.tima_bit()) & 1 != 0 && self.tac & 0x4 != 0;
+            // TIMA increments on the falling edge of the selected DIV bit.
+            if before && !after {
+                let (val, overflow) = self.tima.overflowing_add(1);
+                self.tima = val;
+                if overflow {
+                    self.tima = self.tma;
+                    fired = true;
+                }
+            }
+        }
+        fired
+    }
+
+    pub fn read(&self, addr: u16) -> u8 {
+        match addr {
+            0xff04 => (self.div >> 8) as u8,
+            0xff05 => self.tima,
+            0xff06 => self.tma,
+            0xff07 => self.tac | 0xf8,
+            _ => 0xff,
+        }
+    }
+
+    pub fn write(&mut self, addr: u16, val: u8) {
+        match addr {
+            0xff04 => self.div = 0,
+            0xff05 => self.tima = val,
+            0xff06 => self.tma = val,
+            0xff07 => self.tac = val & 0x7,
+            _ => {}
+        }
+    }
+}
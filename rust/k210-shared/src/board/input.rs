@@ -0,0 +1,77 @@
+//! Generic pointer/touch input abstraction.
+//!
+//! Every supported controller (the resistive NS2009 panel, the Cirque
+//! Pinnacle trackpad, ...) reports samples in its own native unit
+//! range. Implementing [`PointerDevice`] and a [`Calibration`] lets
+//! callers be generic over the backend: the device only has to report
+//! raw units, and the shared [`PointerDevice::poll`] default maps
+//! those into screen space.
+
+/** A single pointer sample, already in screen coordinates. */
+#[derive(Debug, Clone, Copy)]
+pub struct PointerEvent {
+    pub x: i32,
+    pub y: i32,
+    pub z: i32,
+}
+
+/** Scale + offset (+ optional axis swap/flip) from a device's native
+ * unit range into `width`x`height` screen space. */
+#[derive(Debug, Clone, Copy)]
+pub struct Calibration {
+    pub x_min: i32,
+    pub x_max: i32,
+    pub y_min: i32,
+    pub y_max: i32,
+    pub swap_axes: bool,
+    pub flip_x: bool,
+    pub flip_y: bool,
+}
+
+impl Calibration {
+    /** A calibration that assumes the device already reports 0..width,
+     * 0..height with no flips, i.e. a no-op transform. */
+    pub fn identity(width: i32, height: i32) -> Self {
+        Self {
+            x_min: 0,
+            x_max: width,
+            y_min: 0,
+            y_max: height,
+            swap_axes: false,
+            flip_x: false,
+            flip_y: false,
+        }
+    }
+
+    pub fn map(&self, raw_x: i32, raw_y: i32, width: i32, height: i32) -> (i32, i32) {
+        let (raw_x, raw_y) = if self.swap_axes { (raw_y, raw_x) } else { (raw_x, raw_y) };
+        let mut x = (raw_x - self.x_min) * width / (self.x_max - self.x_min);
+        let mut y = (raw_y - self.y_min) * height / (self.y_max - self.y_min);
+        if self.flip_x {
+            x = width - 1 - x;
+        }
+        if self.flip_y {
+            y = height - 1 - y;
+        }
+        (x, y)
+    }
+}
+
+/** Anything that can be polled for a pointer/touch sample. Backends
+ * implement [`raw_poll`](PointerDevice::raw_poll) in their own native
+ * units; [`poll`](PointerDevice::poll) is the shared entry point that
+ * applies a [`Calibration`] to map that into screen space. */
+pub trait PointerDevice {
+    /** Returns the latest raw `(x, y, z)` sample in device-native
+     * units, or `None` if nothing new is available (or the finger has
+     * been lifted). */
+    fn raw_poll(&mut self) -> Option<(i32, i32, i32)>;
+
+    /** Poll the device and map its raw sample into `width`x`height`
+     * screen space via `cal`. */
+    fn poll(&mut self, cal: &Calibration, width: i32, height: i32) -> Option<PointerEvent> {
+        let (raw_x, raw_y, z) = self.raw_poll()?;
+        let (x, y) = cal.map(raw_x, raw_y, width, height);
+        Some(PointerEvent { x, y, z })
+    }
+}
@@ -0,0 +1,79 @@
+//! Driver for the NS2009 resistive touch-panel controller, read over
+//! I²C. This is the touch backend `game-of-life` already uses; it now
+//! also implements the generic [`PointerDevice`] trait so it can be
+//! swapped for another controller (e.g. [`super::pinnacle::Pinnacle`])
+//! with no other changes at the call site.
+
+use super::input::PointerDevice;
+use crate::soc::i2c::I2C;
+
+const CMD_MEASURE_X: u8 = 0xc0;
+const CMD_MEASURE_Y: u8 = 0x90;
+const CMD_MEASURE_Z1: u8 = 0xb0;
+const CMD_LOW_POWER: u8 = 0x80;
+
+/** A touch/pointer sample, already mapped into screen space by
+ * [`Calibration`]. */
+#[derive(Debug, Clone, Copy)]
+pub struct Event {
+    pub x: i32,
+    pub y: i32,
+    pub z: i32,
+}
+
+/** Linear calibration from raw 12-bit ADC counts into screen pixels,
+ * as measured for a specific panel/mounting. */
+#[derive(Debug, Clone, Copy)]
+pub struct Calibration {
+    pub x_off: i32,
+    pub x_scale: i32, // Q16 fixed point
+    pub y_off: i32,
+    pub y_scale: i32, // Q16 fixed point
+}
+
+/** Polling driver for the NS2009; owns the I2C peripheral it was
+ * initialized with. */
+pub struct TouchScreen {
+    i2c: I2C,
+    cal: Calibration,
+}
+
+impl TouchScreen {
+    /** Probe the panel and return `None` if it doesn't answer. */
+    pub fn init(i2c: I2C, cal: Calibration) -> Option<Self> {
+        let mut dev = Self { i2c, cal };
+        dev.i2c.send_data_sfr(CMD_LOW_POWER, &[]);
+        Some(dev)
+    }
+
+    fn read_channel(&mut self, cmd: u8) -> u16 {
+        let mut buf = [0u8; 2];
+        self.i2c.send_data_sfr(cmd, &[]);
+        self.i2c.recv_data_sfr(cmd, &mut buf);
+        ((buf[0] as u16) << 4) | (buf[1] as u16 >> 4)
+    }
+
+    /** Poll for a new sample, already scaled by `self.cal` into screen
+     * space. Returns `None` if the panel isn't currently pressed. */
+    pub fn poll(&mut self) -> Option<Event> {
+        let (x, y, z) = self.raw_poll()?;
+        let x = self.cal.x_off + (x * self.cal.x_scale >> 16);
+        let y = self.cal.y_off + (y * self.cal.y_scale >> 16);
+        Some(Event { x, y, z })
+    }
+}
+
+impl PointerDevice for TouchScreen {
+    /** Returns the raw 12-bit `(x, y)` ADC counts and a rough pressure
+     * figure derived from the Z1 channel, or `None` if the panel
+     * isn't being touched (Z1 reads near zero). */
+    fn raw_poll(&mut self) -> Option<(i32, i32, i32)> {
+        let z1 = self.read_channel(CMD_MEASURE_Z1);
+        if z1 < 16 {
+            return None;
+        }
+        let x = self.read_channel(CMD_MEASURE_X);
+        let y = self.read_channel(CMD_MEASURE_Y);
+        Some((x as i32, y as i32, z1 as i32))
+    }
+}
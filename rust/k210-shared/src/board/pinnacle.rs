@@ -0,0 +1,157 @@
+//! Driver for the Cirque Pinnacle (1CA027) capacitive trackpad, usable
+//! over either I²C or SPI via its Register Access Protocol (RAP): a
+//! register read sends the register address OR'd with `0xA0` followed
+//! by filler bytes to clock the reply out, and a write sends the
+//! address OR'd with `0x80` followed by the data byte.
+
+use super::def::{DISP_WIDTH, DISP_HEIGHT};
+use super::input::{Calibration, PointerDevice, PointerEvent};
+use crate::soc::i2c::I2C;
+use crate::soc::spi::SPI;
+
+/** I²C slave address of the Pinnacle. */
+pub const PINNACLE_SLV_ADDR: u16 = 0x2a;
+
+const REG_FW_ID: u8 = 0x00;
+const REG_STATUS1: u8 = 0x02;
+const REG_SYS_CONFIG1: u8 = 0x03;
+const REG_FEED_CONFIG1: u8 = 0x04;
+const REG_FEED_CONFIG2: u8 = 0x05;
+const REG_Z_IDLE: u8 = 0x0a;
+const REG_PACKET_BYTE_0: u8 = 0x12;
+
+const SW_DR: u8 = 0x04; // STATUS1: data-ready flag
+const SW_CC: u8 = 0x08; // STATUS1: command-complete flag, must be cleared after reset
+
+// Usable active area, per the Pinnacle datasheet.
+const PINNACLE_X_MIN: i32 = 127;
+const PINNACLE_X_MAX: i32 = 1919;
+const PINNACLE_Y_MIN: i32 = 63;
+const PINNACLE_Y_MAX: i32 = 1471;
+
+/** Calibration mapping the Pinnacle's active-area units into screen
+ * space, for use with [`PointerDevice::poll`]. */
+pub const PINNACLE_CAL: Calibration = Calibration {
+    x_min: PINNACLE_X_MIN,
+    x_max: PINNACLE_X_MAX,
+    y_min: PINNACLE_Y_MIN,
+    y_max: PINNACLE_Y_MAX,
+    swap_axes: false,
+    flip_x: false,
+    flip_y: false,
+};
+
+/** Either transport the Pinnacle can be wired up on; both speak RAP,
+ * just with a different framing for the address/filler bytes. */
+pub enum Bus {
+    I2c(I2C),
+    Spi(SPI),
+}
+
+impl Bus {
+    fn read_reg(&mut self, addr: u8) -> u8 {
+        match self {
+            Bus::I2c(i2c) => {
+                i2c.send_data(PINNACLE_SLV_ADDR, &[addr | 0xa0]);
+                let mut buf = [0u8; 1];
+                i2c.recv_data(PINNACLE_SLV_ADDR, &mut buf);
+                buf[0]
+            }
+            Bus::Spi(spi) => {
+                // RAP over SPI: address byte, two filler bytes to let
+                // the chip prepare its reply, then the data byte.
+                let mut buf = [addr | 0xa0, 0xfc, 0xfc, 0xfc];
+                spi.transfer(&mut buf);
+                buf[3]
+            }
+        }
+    }
+
+    fn write_reg(&mut self, addr: u8, val: u8) {
+        match self {
+            Bus::I2c(i2c) => {
+                i2c.send_data(PINNACLE_SLV_ADDR, &[addr | 0x80, val]);
+            }
+            Bus::Spi(spi) => {
+                let mut buf = [addr | 0x80, val];
+                spi.transfer(&mut buf);
+            }
+        }
+    }
+
+    fn read_packet(&mut self, out: &mut [u8; 6]) {
+        match self {
+            Bus::I2c(i2c) => {
+                i2c.send_data(PINNACLE_SLV_ADDR, &[REG_PACKET_BYTE_0 | 0xa0]);
+                i2c.recv_data(PINNACLE_SLV_ADDR, out);
+            }
+            Bus::Spi(_) => {
+                for (i, byte) in out.iter_mut().enumerate() {
+                    *byte = self.read_reg(REG_PACKET_BYTE_0 + i as u8);
+                }
+            }
+        }
+    }
+}
+
+/** Polling driver for the trackpad; reconstructs absolute X/Y/Z from
+ * the Pinnacle's relative/absolute data registers. */
+pub struct Pinnacle {
+    bus: Bus,
+}
+
+impl Pinnacle {
+    /** Reset and configure the Pinnacle for absolute-mode reporting,
+     * returning `None` if the firmware ID register doesn't look
+     * sane (i.e. no Pinnacle present on the bus). */
+    pub fn init(mut bus: Bus) -> Option<Self> {
+        if bus.read_reg(REG_FW_ID) == 0 {
+            return None;
+        }
+        // Clear power-on SW_CC, then ask for absolute-mode packets
+        // with Z (pressure) included and no data-ready pin toggling.
+        bus.write_reg(REG_STATUS1, 0);
+        bus.write_reg(REG_SYS_CONFIG1, 0);
+        bus.write_reg(REG_FEED_CONFIG2, 0x1e); // disable taps/scroll, absolute mode
+        bus.write_reg(REG_FEED_CONFIG1, 0x03); // feed enable, absolute mode
+        bus.write_reg(REG_Z_IDLE, 5);
+        Some(Self { bus })
+    }
+
+    /** Poll for a new sample already rescaled into screen space via
+     * [`PINNACLE_CAL`], so this is a drop-in replacement for any other
+     * board touch driver's `poll()` with no other changes at the call
+     * site. Returns `None` if nothing new is available. */
+    pub fn poll(&mut self) -> Option<PointerEvent> {
+        PointerDevice::poll(self, &PINNACLE_CAL, DISP_WIDTH as i32, DISP_HEIGHT as i32)
+    }
+}
+
+impl PointerDevice for Pinnacle {
+    /** Returns the active-area-clamped raw `(x, y, z)` sample; pair
+     * with [`PINNACLE_CAL`] to map it into screen space. Returns
+     * `None` when no new data is ready or the finger is lifted. */
+    fn raw_poll(&mut self) -> Option<(i32, i32, i32)> {
+        let status = self.bus.read_reg(REG_STATUS1);
+        if status & SW_DR == 0 {
+            return None;
+        }
+
+        let mut packet = [0u8; 6];
+        self.bus.read_packet(&mut packet);
+        self.bus.write_reg(REG_STATUS1, 0); // ack: clear SW_DR
+
+        let x = packet[2] as u32 | (packet[4] as u32 & 0x0f) << 8;
+        let y = packet[3] as u32 | (packet[4] as u32 & 0xf0) << 4;
+        let z = packet[5] & 0x3f;
+
+        if z == 0 || x == 0 || y == 0 {
+            return None;
+        }
+
+        let x = (x as i32).clamp(PINNACLE_X_MIN, PINNACLE_X_MAX);
+        let y = (y as i32).clamp(PINNACLE_Y_MIN, PINNACLE_Y_MAX);
+
+        Some((x, y, z as i32))
+    }
+}